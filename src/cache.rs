@@ -0,0 +1,104 @@
+//! 以內容雜湊定址（content-addressable）的下載快取
+//!
+//! 過去 `fetch_package` 每次都重新下載到 `env::temp_dir()`，同一個檔案在
+//! 不同套件間完全無法共用頻寬。這個模組依驗證過的 [`Integrity`] 雜湊把
+//! 檔案存放在快取根目錄下，重複安裝可以直接命中快取、不必再下載套件檔案；同時
+//! 維護一份小索引，記錄「套件名稱+版本」對應到哪一個內容雜湊，讓呼叫端
+//! 不需要重新取得 `PackageBasicInfo` 就能查出某個已知版本快取在哪裡。
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::{CoreResult, Integrity, JsonStorage};
+
+/// 內容雜湊定址的下載快取
+pub struct Cache {
+    root: PathBuf,
+    /// 「套件名稱@版本」到內容雜湊（SRI 字串）的索引檔路徑
+    index_path: PathBuf,
+}
+
+impl Cache {
+    /// 以指定的根目錄開啟（或建立）一個快取
+    pub fn new(root: impl Into<PathBuf>) -> CoreResult<Self> {
+        let root = root.into();
+        fs::create_dir_all(root.join("content"))?;
+        fs::create_dir_all(root.join("tmp"))?;
+        let index_path = root.join("index.json");
+        if !index_path.is_file() {
+            JsonStorage::to_json(&HashMap::<String, String>::new(), &index_path)?;
+        }
+        Ok(Cache { root, index_path })
+    }
+
+    /// 依完整性雜湊算出此內容應該存放的路徑，格式為
+    /// `<root>/content/<algo>/<前兩碼>/<其餘位元組>`
+    fn content_path(&self, integrity: &Integrity) -> CoreResult<PathBuf> {
+        let hex_digest = integrity.digest_hex()?;
+        let split_at = hex_digest.len().min(2);
+        let (prefix, rest) = hex_digest.split_at(split_at);
+        Ok(self
+            .root
+            .join("content")
+            .join(integrity.algorithm.to_string())
+            .join(prefix)
+            .join(rest))
+    }
+
+    /// 查詢快取：若內容已存在回傳其路徑，否則回傳 `None`
+    pub fn get(&self, integrity: &Integrity) -> CoreResult<Option<PathBuf>> {
+        let path = self.content_path(integrity)?;
+        Ok(if path.is_file() { Some(path) } else { None })
+    }
+
+    /// 將已驗證過的內容寫入快取，並在名稱+版本索引中記錄對應的內容雜湊
+    ///
+    /// 內容本身採「先寫到暫存檔，再更名為最終路徑」，確保其他行程看到的
+    /// 檔案要嘛不存在、要嘛是完整內容，不會讀到寫到一半的內容。
+    pub fn put(
+        &self,
+        name: &str,
+        version: &str,
+        integrity: &Integrity,
+        bytes: &[u8],
+    ) -> CoreResult<PathBuf> {
+        let final_path = self.content_path(integrity)?;
+        if !final_path.is_file() {
+            if let Some(parent) = final_path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let hex_digest = integrity.digest_hex()?;
+            let tmp_path = self.root.join("tmp").join(format!("{hex_digest}.tmp"));
+            fs::write(&tmp_path, bytes)?;
+            fs::rename(&tmp_path, &final_path)?;
+        }
+        self.record(name, version, integrity)?;
+        Ok(final_path)
+    }
+
+    /// 在名稱+版本索引中記錄這個套件版本目前對應到的內容雜湊
+    fn record(&self, name: &str, version: &str, integrity: &Integrity) -> CoreResult<()> {
+        let mut index = self.load_index()?;
+        index.insert(format!("{name}@{version}"), integrity.to_string());
+        JsonStorage::to_json(&index, &self.index_path)
+    }
+
+    /// 依套件名稱+版本查詢先前記錄的內容雜湊
+    pub fn lookup(&self, name: &str, version: &str) -> CoreResult<Option<Integrity>> {
+        let index = self.load_index()?;
+        index
+            .get(&format!("{name}@{version}"))
+            .map(|s| Integrity::from_str(s))
+            .transpose()
+    }
+
+    fn load_index(&self) -> CoreResult<HashMap<String, String>> {
+        JsonStorage::from_json(&self.index_path)
+    }
+
+    /// 快取的根目錄
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+}
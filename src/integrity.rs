@@ -0,0 +1,170 @@
+//! Subresource Integrity（SRI）風格的雜湊格式與串流驗證
+//!
+//! `PackageBasicInfo.hash` 過去只是一個不透明的字串，`fetch_package` 下載
+//! 完也從未驗證過內容。這個模組把雜湊值變成一個可以辨識演算法、格式為
+//! `<algo>-<base64 digest>`（與瀏覽器的 SRI 相同）的 [`Integrity`] 型別，
+//! 並提供邊下載邊計算雜湊用的 [`IntegrityHasher`]。
+use std::fmt;
+use std::io::Read;
+use std::path::Path;
+use std::str::FromStr;
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use sha2::{Digest, Sha256, Sha512};
+
+use crate::{CoreError, CoreResult};
+
+/// 支援的雜湊演算法
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashAlgorithm {
+    Sha256,
+    Sha512,
+}
+
+impl HashAlgorithm {
+    fn as_str(self) -> &'static str {
+        match self {
+            HashAlgorithm::Sha256 => "sha256",
+            HashAlgorithm::Sha512 => "sha512",
+        }
+    }
+}
+
+impl fmt::Display for HashAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for HashAlgorithm {
+    type Err = CoreError;
+
+    fn from_str(s: &str) -> CoreResult<Self> {
+        match s {
+            "sha256" => Ok(HashAlgorithm::Sha256),
+            "sha512" => Ok(HashAlgorithm::Sha512),
+            other => Err(CoreError::InvalidPackage(format!(
+                "unsupported hash algorithm '{other}'"
+            ))),
+        }
+    }
+}
+
+/// 一個 SRI 風格的完整性雜湊，格式為 `<algo>-<base64 digest>`，
+/// 例如 `sha256-47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU=`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Integrity {
+    pub algorithm: HashAlgorithm,
+    pub digest: String,
+}
+
+impl fmt::Display for Integrity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.algorithm, self.digest)
+    }
+}
+
+impl FromStr for Integrity {
+    type Err = CoreError;
+
+    fn from_str(s: &str) -> CoreResult<Self> {
+        let (algo, digest) = s
+            .split_once('-')
+            .ok_or_else(|| CoreError::InvalidPackage(format!("invalid integrity string '{s}'")))?;
+        if digest.is_empty() {
+            return Err(CoreError::InvalidPackage(format!(
+                "invalid integrity string '{s}'"
+            )));
+        }
+        Ok(Integrity {
+            algorithm: HashAlgorithm::from_str(algo)?,
+            digest: digest.to_string(),
+        })
+    }
+}
+
+impl Integrity {
+    /// 針對整個 reader 的內容計算指定演算法的雜湊
+    pub fn from_reader<R: Read>(algorithm: HashAlgorithm, reader: &mut R) -> CoreResult<Integrity> {
+        let mut hasher = IntegrityHasher::new(algorithm);
+        let mut buf = [0u8; 8192];
+        loop {
+            let read = reader.read(&mut buf)?;
+            if read == 0 {
+                break;
+            }
+            hasher.update(&buf[..read]);
+        }
+        Ok(Integrity {
+            algorithm,
+            digest: hasher.finalize_base64(),
+        })
+    }
+
+    /// 重新計算本地檔案的雜湊，確認它仍與這個完整性雜湊相符
+    pub fn verify_file(&self, path: &Path) -> CoreResult<()> {
+        let mut file = std::fs::File::open(path)?;
+        let actual = Integrity::from_reader(self.algorithm, &mut file)?;
+        self.verify(&actual.digest)
+    }
+
+    /// 將已經算好的 base64 摘要跟這個完整性雜湊比對
+    pub fn verify(&self, actual_digest: &str) -> CoreResult<()> {
+        if self.digest == actual_digest {
+            Ok(())
+        } else {
+            Err(CoreError::HashMismatch {
+                expected: self.to_string(),
+                actual: format!("{}-{}", self.algorithm, actual_digest),
+            })
+        }
+    }
+
+    /// 將 base64 摘要解碼回原始位元組
+    pub fn digest_bytes(&self) -> CoreResult<Vec<u8>> {
+        STANDARD
+            .decode(&self.digest)
+            .map_err(|e| CoreError::InvalidPackage(format!("invalid integrity digest: {e}")))
+    }
+
+    /// 將摘要轉成固定長度的十六進位字串，適合用來組出檔案系統路徑
+    /// （base64 摘要可能含有 `/`，不能直接當路徑片段）
+    pub fn digest_hex(&self) -> CoreResult<String> {
+        Ok(self
+            .digest_bytes()?
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect())
+    }
+}
+
+/// 邊下載邊計算雜湊用的串流雜湊器，對應 [`Integrity`] 支援的演算法
+pub enum IntegrityHasher {
+    Sha256(Box<Sha256>),
+    Sha512(Box<Sha512>),
+}
+
+impl IntegrityHasher {
+    pub fn new(algorithm: HashAlgorithm) -> Self {
+        match algorithm {
+            HashAlgorithm::Sha256 => IntegrityHasher::Sha256(Box::new(Sha256::new())),
+            HashAlgorithm::Sha512 => IntegrityHasher::Sha512(Box::new(Sha512::new())),
+        }
+    }
+
+    /// 餵入下一個已下載的區塊
+    pub fn update(&mut self, chunk: &[u8]) {
+        match self {
+            IntegrityHasher::Sha256(h) => h.update(chunk),
+            IntegrityHasher::Sha512(h) => h.update(chunk),
+        }
+    }
+
+    /// 結束雜湊運算並回傳 base64 編碼後的摘要
+    pub fn finalize_base64(self) -> String {
+        match self {
+            IntegrityHasher::Sha256(h) => STANDARD.encode(h.finalize()),
+            IntegrityHasher::Sha512(h) => STANDARD.encode(h.finalize()),
+        }
+    }
+}
@@ -1,16 +1,48 @@
+mod cache;
 mod error;
+mod integrity;
+mod lockfile;
+mod resolver;
+mod version;
+pub use cache::*;
 pub use error::*;
+pub use integrity::*;
+pub use lockfile::*;
+pub use resolver::*;
+pub use version::*;
 use futures_util::StreamExt;
 use serde::{Deserialize, Serialize};
 use serde_json::to_writer_pretty;
-use std::{collections::HashMap, env, io::Read, path::Path};
-use tokio::io::AsyncWriteExt;
+use std::{collections::HashMap, env, io::Read, path::Path, str::FromStr};
+
+/// 依賴的種類，對應套件登錄中心常見的依賴分組語意
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DependencyKind {
+    /// 執行期必要依賴（預設）
+    #[default]
+    Runtime,
+    /// 僅開發/測試時需要的依賴
+    Dev,
+    /// 可選依賴，解析失敗時略過而非報錯
+    Optional,
+    /// 對等依賴：不會被安裝，只檢查是否已有相容版本被解析
+    Peer,
+}
+
+impl DependencyKind {
+    fn is_default(&self) -> bool {
+        *self == DependencyKind::Runtime
+    }
+}
 
 /// 代表套件的依賴資訊
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Dependency {
     pub name: String,
     pub version: String,
+    /// 依賴種類，預設為 `Runtime`；為了向後相容，序列化時省略預設值
+    #[serde(default, skip_serializing_if = "DependencyKind::is_default")]
+    pub kind: DependencyKind,
 }
 /// 儲存套件的完整資訊
 #[derive(Debug, Serialize, Deserialize)]
@@ -121,8 +153,19 @@ where
 /// 儲存庫的資訊管理模組
 #[derive(Debug, Serialize, Deserialize, Default)]
 pub struct RepoInfo {
-    /// 儲存庫內的套件映射
-    packages: HashMap<String, PackageBasicInfo>,
+    /// 儲存庫內的套件映射，每個名稱底下收錄該套件的所有版本
+    packages: HashMap<String, PackageVersions>,
+}
+/// 單一套件名稱底下收錄的所有版本，以及其 dist-tag 對應
+///
+/// 對應一般套件登錄中心「一個套件可以同時發佈多個版本，再用
+/// `latest`/`beta`/`stable` 這類標籤指向其中一個版本」的模型。
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct PackageVersions {
+    /// 依版本號索引的套件資訊
+    pub versions: HashMap<Version, PackageBasicInfo>,
+    /// 符號標籤（例如 `latest`、`beta`）對應的具體版本字串
+    pub dist_tags: HashMap<String, String>,
 }
 #[derive(Debug, Serialize, Deserialize)]
 /// 套件的基本資訊
@@ -152,7 +195,7 @@ impl RepoInfo {
             packages: HashMap::new(),
         }
     }
-    /// 檢查是否存在指定名稱的套件
+    /// 檢查是否存在指定名稱的套件（任一版本）
     ///
     /// # 參數
     /// - `package_name`: 套件名稱
@@ -160,9 +203,11 @@ impl RepoInfo {
     /// # 回傳
     /// 若存在回傳 `true`，否則回傳 `false`
     pub fn has_package(&self, package_name: &str) -> bool {
-        self.packages.contains_key(package_name)
+        self.packages
+            .get(package_name)
+            .is_some_and(|versions| !versions.versions.is_empty())
     }
-    /// 根據名稱獲取套件
+    /// 根據名稱獲取套件，回傳 `latest` 標籤指向的版本
     ///
     /// # 參數
     /// - `package_name`: 套件名稱
@@ -170,14 +215,80 @@ impl RepoInfo {
     /// # 回傳
     /// 回傳套件資訊或錯誤
     pub fn get_package(&self, package_name: &str) -> CoreResult<&PackageBasicInfo> {
-        match self.packages.get(package_name) {
-            Some(package) => Ok(package),
-            None => Err(CoreError::PackageNotFound(package_name.to_string())),
+        if let Ok(tagged) = self.get_tagged(package_name, "latest") {
+            return Ok(tagged);
         }
+        let versions = self
+            .packages
+            .get(package_name)
+            .ok_or_else(|| CoreError::PackageNotFound(package_name.to_string()))?;
+        versions
+            .versions
+            .keys()
+            .max()
+            .and_then(|v| versions.versions.get(v))
+            .ok_or_else(|| CoreError::PackageNotFound(package_name.to_string()))
     }
-    pub fn get_package_handler(&self) -> &HashMap<String, PackageBasicInfo> {
+    /// 依 dist-tag（例如 `latest`、`beta`）取得套件的特定版本
+    ///
+    /// # 參數
+    /// - `package_name`: 套件名稱
+    /// - `tag`: dist-tag 名稱
+    ///
+    /// # 回傳
+    /// 回傳該標籤目前指向的套件版本，若套件或標籤不存在則回傳錯誤
+    pub fn get_tagged(&self, package_name: &str, tag: &str) -> CoreResult<&PackageBasicInfo> {
+        let versions = self
+            .packages
+            .get(package_name)
+            .ok_or_else(|| CoreError::PackageNotFound(package_name.to_string()))?;
+        let tagged_version = versions
+            .dist_tags
+            .get(tag)
+            .ok_or_else(|| CoreError::PackageNotFound(format!("{package_name}@{tag}")))?;
+        let version = Version::from_str(tagged_version)?;
+        versions.versions.get(&version).ok_or_else(|| {
+            CoreError::VersionMismatch(format!(
+                "dist-tag '{tag}' of '{package_name}' points to missing version '{tagged_version}'"
+            ))
+        })
+    }
+    pub fn get_package_handler(&self) -> &HashMap<String, PackageVersions> {
         &self.packages
     }
+    /// 依照版本需求，在套件已收錄的所有版本中解析出最佳匹配
+    ///
+    /// # 參數
+    /// - `package_name`: 套件名稱
+    /// - `req`: 版本需求字串，支援 `^`/`~`/範圍/`*`/`latest`，詳見 [`VersionReq`]
+    ///
+    /// # 回傳
+    /// 回傳滿足需求的最高版本，若無任何版本滿足則回傳 `CoreError::VersionMismatch`
+    pub fn resolve_version(&self, package_name: &str, req: &str) -> CoreResult<&PackageBasicInfo> {
+        let requirement = VersionReq::from_str(req).map_err(|_| {
+            CoreError::VersionMismatch(format!(
+                "invalid version requirement '{req}' for '{package_name}'"
+            ))
+        })?;
+        if requirement == VersionReq::Latest {
+            return self.get_tagged(package_name, "latest");
+        }
+        let versions = self
+            .packages
+            .get(package_name)
+            .ok_or_else(|| CoreError::PackageNotFound(package_name.to_string()))?;
+        versions
+            .versions
+            .keys()
+            .filter(|v| requirement.matches(v))
+            .max()
+            .and_then(|v| versions.versions.get(v))
+            .ok_or_else(|| {
+                CoreError::VersionMismatch(format!(
+                    "'{package_name}' has no version satisfying '{req}'"
+                ))
+            })
+    }
 }
 #[cfg(feature = "server")]
 #[allow(clippy::too_many_arguments)]
@@ -209,64 +320,119 @@ impl RepoInfo {
             #[cfg(feature = "client")]
             description,
         };
-        self.packages.insert(name, package);
+        self.add_package_with_info(name, package);
     }
-    /// 透過 `PackageBasicInfo` 新增一個套件
+    /// 透過 `PackageBasicInfo` 新增一個套件版本
+    ///
+    /// 若這是該套件的第一個版本，或這個版本高於目前 `latest` 標籤指向的
+    /// 版本，`latest` 標籤會自動移至這個版本，與一般套件登錄中心發佈新
+    /// 版本時的行為一致。
     pub fn add_package_with_info(&mut self, name: String, info: PackageBasicInfo) {
-        self.packages.insert(name, info);
+        let Ok(version) = Version::from_str(&info.version) else {
+            return;
+        };
+        let entry = self.packages.entry(name).or_default();
+        let should_bump_latest = match entry.dist_tags.get("latest") {
+            Some(current) => Version::from_str(current).is_ok_and(|current| version > current),
+            None => true,
+        };
+        if should_bump_latest {
+            entry.dist_tags.insert("latest".to_string(), version.to_string());
+        }
+        entry.versions.insert(version, info);
+    }
+
+    /// 將一個 dist-tag 指向套件的某個已收錄版本
+    pub fn set_dist_tag(&mut self, package_name: &str, tag: &str, version: &str) -> CoreResult<()> {
+        let parsed = Version::from_str(version)?;
+        let versions = self
+            .packages
+            .get_mut(package_name)
+            .ok_or_else(|| CoreError::PackageNotFound(package_name.to_string()))?;
+        if !versions.versions.contains_key(&parsed) {
+            return Err(CoreError::VersionMismatch(format!(
+                "'{package_name}' has no published version '{version}'"
+            )));
+        }
+        versions.dist_tags.insert(tag.to_string(), parsed.to_string());
+        Ok(())
     }
 
-    /// 根據名稱移除套件
-    pub fn remove_package(&mut self, package_name: &str) -> CoreResult<PackageBasicInfo> {
-        match self.packages.remove(package_name) {
-            Some(package) => Ok(package),
-            None => Err(CoreError::PackageNotFound(package_name.to_string())),
+    /// 移除套件的特定版本；若移除後該套件已無任何版本，則一併移除整個套件項目
+    pub fn remove_package(&mut self, package_name: &str, version: &str) -> CoreResult<PackageBasicInfo> {
+        let parsed = Version::from_str(version)?;
+        let versions = self
+            .packages
+            .get_mut(package_name)
+            .ok_or_else(|| CoreError::PackageNotFound(package_name.to_string()))?;
+        let removed = versions
+            .versions
+            .remove(&parsed)
+            .ok_or_else(|| CoreError::PackageNotFound(format!("{package_name}@{version}")))?;
+        let normalized = parsed.to_string();
+        versions.dist_tags.retain(|_, v| v.as_str() != normalized);
+        if versions.versions.is_empty() {
+            self.packages.remove(package_name);
         }
+        Ok(removed)
     }
-    /// 更新儲存庫中的套件資訊
+    /// 更新儲存庫中某個套件版本的資訊
     pub fn update_package(
         &mut self,
         package_name: &str,
+        version: &str,
         url: Option<String>,
         file_name: Option<String>,
-        version: Option<String>,
         hash: Option<String>,
         dependencies: Option<Vec<Dependency>>,
         #[cfg(feature = "client")] entry: Option<String>,
         #[cfg(feature = "client")] description: Option<String>,
-    ) {
-        if let Some(existing_package) = self.packages.get_mut(package_name) {
+    ) -> CoreResult<()> {
+        let parsed = Version::from_str(version)?;
+        let package_versions = self.packages.entry(package_name.to_string()).or_default();
+        if let Some(existing_package) = package_versions.versions.get_mut(&parsed) {
             if let Some(new_url) = url {
                 existing_package.url = new_url;
             }
             if let Some(new_file_name) = file_name {
                 existing_package.file_name = new_file_name;
             }
-            if let Some(new_version) = version {
-                existing_package.version = new_version;
-            }
             if let Some(new_hash) = hash {
                 existing_package.hash = new_hash;
             }
             if let Some(new_dependencies) = dependencies {
                 existing_package.dependencies = Some(new_dependencies);
             }
+            #[cfg(feature = "client")]
+            {
+                if let Some(new_entry) = entry {
+                    existing_package.entry = Some(new_entry);
+                }
+                if let Some(new_description) = description {
+                    existing_package.description = Some(new_description);
+                }
+            }
         } else {
-            self.packages.insert(
-                package_name.to_string(),
+            package_versions.versions.insert(
+                parsed.clone(),
                 PackageBasicInfo {
                     url: url.unwrap_or_default(),
                     file_name: file_name.unwrap_or_default(),
-                    version: version.unwrap_or_default(),
+                    version: version.to_string(),
                     hash: hash.unwrap_or_default(),
-                    dependencies: None,
+                    dependencies,
                     #[cfg(feature = "client")]
                     entry,
                     #[cfg(feature = "client")]
                     description,
                 },
             );
+            package_versions
+                .dist_tags
+                .entry("latest".to_string())
+                .or_insert_with(|| parsed.to_string());
         }
+        Ok(())
     }
 }
 
@@ -277,52 +443,78 @@ impl RepoInfo {
         self.packages = repo_info.packages;
         Ok(())
     }
-    pub async fn fetch_package(&self, pkg_name: &str) -> CoreResult<PackageInfo> {
-        if let Some(package) = self.packages.get(pkg_name) {
-            let url = package.url.as_str();
-            let package_info: PackageInfo = JsonStorage::from_url(url).await?;
-            let req = reqwest::get(url)
-                .await
-                .map_err(|e| CoreError::NetworkError(e.to_string()))?;
-            if !req.status().is_success() {
-                return Err(CoreError::NetworkError(format!(
-                    "Failed to fetch package '{}': {}",
-                    pkg_name,
-                    req.status()
-                )));
-            }
-            let filename = env::temp_dir().join(package.file_name.as_str());
-            let mut file = tokio::fs::File::create(&filename).await?;
-            let mut stream = req.bytes_stream();
-            while let Some(item) = stream.next().await {
-                let chunk = item.map_err(|e| CoreError::NetworkError(e.to_string()))?;
-                file.write_all(&chunk).await?;
-            }
+    /// 下載套件並以內容雜湊快取
+    ///
+    /// 先從套件的 `url` 取得 `PackageInfo`（這一步每次呼叫都會發出網路請求，
+    /// 快取命中時也一樣），再以套件已宣告的完整性雜湊查詢 `cache`；命中時
+    /// 直接把快取檔案複製到暫存目錄，不再下載套件檔案本身。未命中時才下載、
+    /// 邊收邊算雜湊、驗證後再寫入快取與暫存目錄，讓下一次安裝可以重複利用。
+    pub async fn fetch_package(&self, pkg_name: &str, cache: &Cache) -> CoreResult<PackageInfo> {
+        let package = self.get_package(pkg_name)?;
+        let url = package.url.as_str();
+        let package_info: PackageInfo = JsonStorage::from_url(url).await?;
+        let integrity = Integrity::from_str(&package.hash)?;
+        let filename = env::temp_dir().join(package.file_name.as_str());
 
-            Ok(package_info)
-        } else {
-            Err(CoreError::PackageNotFound(pkg_name.to_string()))
+        if let Some(cached_path) = cache.get(&integrity)? {
+            tokio::fs::copy(&cached_path, &filename).await?;
+            return Ok(package_info);
+        }
+
+        let req = reqwest::get(url)
+            .await
+            .map_err(|e| CoreError::NetworkError(e.to_string()))?;
+        if !req.status().is_success() {
+            return Err(CoreError::NetworkError(format!(
+                "Failed to fetch package '{}': {}",
+                pkg_name,
+                req.status()
+            )));
         }
+        let mut hasher = IntegrityHasher::new(integrity.algorithm);
+        let mut bytes = Vec::new();
+        let mut stream = req.bytes_stream();
+        while let Some(item) = stream.next().await {
+            let chunk = item.map_err(|e| CoreError::NetworkError(e.to_string()))?;
+            hasher.update(&chunk);
+            bytes.extend_from_slice(&chunk);
+        }
+
+        let actual_digest = hasher.finalize_base64();
+        integrity.verify(&actual_digest)?;
+
+        cache.put(pkg_name, &package.version, &integrity, &bytes)?;
+        tokio::fs::write(&filename, &bytes).await?;
+
+        Ok(package_info)
     }
     pub async fn get_single_package_info(&self, pkg_name: &str) -> CoreResult<PackageInfo> {
-        if let Some(package) = self.packages.get(pkg_name) {
-            let url = package.url.as_str();
-            let new_url = url.replace(
-                &package.file_name,
-                format!("src/{}/packageInfo.json", pkg_name).as_str(),
-            );
-            let package_info: PackageInfo = JsonStorage::from_url(&new_url).await?;
-            Ok(package_info)
-        } else {
-            Err(CoreError::PackageNotFound(pkg_name.to_string()))
-        }
+        let package = self.get_package(pkg_name)?;
+        let url = package.url.as_str();
+        let new_url = url.replace(
+            &package.file_name,
+            format!("src/{}/packageInfo.json", pkg_name).as_str(),
+        );
+        let package_info: PackageInfo = JsonStorage::from_url(&new_url).await?;
+        Ok(package_info)
     }
 }
 impl Dependency {
+    /// 建立一個 `Runtime` 依賴
     pub fn new(name: &str, version: &str) -> Self {
         Dependency {
             name: name.to_owned(),
             version: version.to_owned(),
+            kind: DependencyKind::Runtime,
+        }
+    }
+
+    /// 建立一個指定種類的依賴
+    pub fn with_kind(name: &str, version: &str, kind: DependencyKind) -> Self {
+        Dependency {
+            name: name.to_owned(),
+            version: version.to_owned(),
+            kind,
         }
     }
 }
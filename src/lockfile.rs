@@ -0,0 +1,85 @@
+//! 凍結已解析依賴集合的 lockfile
+//!
+//! 同一個 `RepoInfo` 在兩次安裝之間可能已經更新，單靠 [`RepoInfo::resolve_tree`]
+//! 重新解析無法保證兩次安裝得到一模一樣的結果。[`LockFile`] 把解析器的輸出
+//! 原封不動記錄下來，之後安裝可以直接依照 lockfile 走，不再重新解析；
+//! 它是一般的 `serde` 結構，因此可以直接透過 [`crate::JsonStorage`] 讀寫。
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{CoreError, CoreResult, RepoInfo, ResolvedPackage, Version};
+
+/// 目前支援的 lockfile 格式版本，用於未來格式變更時的相容性判斷
+pub const LOCKFILE_FORMAT_VERSION: u32 = 1;
+
+/// lockfile 中單一套件的凍結紀錄
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LockedPackage {
+    /// 套件名稱
+    pub name: String,
+    /// 解析當下的確切版本
+    pub version: String,
+    /// 解析當下的下載 URL
+    pub url: String,
+    /// 解析當下的完整性雜湊
+    pub hash: String,
+}
+
+/// 凍結的依賴解析結果，可序列化為 JSON 存放在專案中（例如 `dpm-lock.json`）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LockFile {
+    /// lockfile 格式版本
+    pub lockfile_version: u32,
+    /// 依解析順序排列的套件紀錄
+    pub packages: Vec<LockedPackage>,
+}
+
+impl LockFile {
+    /// 由 [`RepoInfo::resolve_tree`] 的輸出產生一份 lockfile
+    pub fn from_resolution(resolved: &[ResolvedPackage]) -> Self {
+        LockFile {
+            lockfile_version: LOCKFILE_FORMAT_VERSION,
+            packages: resolved
+                .iter()
+                .map(|package| LockedPackage {
+                    name: package.name.clone(),
+                    version: package.version.to_string(),
+                    url: package.url.clone(),
+                    hash: package.hash.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    /// 確認 lockfile 中每一筆紀錄在 `repo` 裡仍然存在且沒有漂移
+    ///
+    /// 套件被下架或版本不再存在回傳 `CoreError::VersionMismatch`；
+    /// 版本仍在但雜湊已變更（例如該版本被重新發布）回傳
+    /// `CoreError::HashMismatch`。紀錄中的下載 URL 不列入比對，URL 變更
+    /// 不會被回報。
+    pub fn verify(&self, repo: &RepoInfo) -> CoreResult<()> {
+        for locked in &self.packages {
+            let versions = repo.get_package_handler().get(&locked.name).ok_or_else(|| {
+                CoreError::VersionMismatch(format!(
+                    "'{}' no longer exists in the repo",
+                    locked.name
+                ))
+            })?;
+            let version = Version::from_str(&locked.version)?;
+            let package = versions.versions.get(&version).ok_or_else(|| {
+                CoreError::VersionMismatch(format!(
+                    "'{}' no longer has version '{}' published",
+                    locked.name, locked.version
+                ))
+            })?;
+            if package.hash != locked.hash {
+                return Err(CoreError::HashMismatch {
+                    expected: locked.hash.clone(),
+                    actual: package.hash.clone(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,144 @@
+//! 遞迴（transitive）依賴解析
+//!
+//! `PackageBasicInfo.dependencies` 只是一份清單，真正要把整棵依賴樹攤平、
+//! 挑出每個套件該安裝的版本，需要走訪這份清單並處理版本衝突與循環依賴，
+//! 這個模組負責這件事。
+use std::collections::{HashMap, VecDeque};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{CoreError, CoreResult, DependencyKind, RepoInfo, Version, VersionReq};
+
+/// 解析完成、可依序安裝的套件
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ResolvedPackage {
+    /// 套件名稱
+    pub name: String,
+    /// 解析出的具體版本
+    pub version: Version,
+    /// 下載 URL
+    pub url: String,
+    /// 套件檔案的雜湊值
+    pub hash: String,
+}
+
+impl RepoInfo {
+    /// 解析一個套件與其遞迴依賴，回傳一份攤平、去重、依解析順序排列的安裝清單
+    ///
+    /// 以 `VecDeque` 進行廣度優先走訪：每次取出 `(套件名稱, 版本需求)`，
+    /// 透過 [`RepoInfo::resolve_version`] 找出符合需求的版本，並將其依賴
+    /// 加入佇列尾端。已選定版本的套件記錄在一張表中；若同一個套件被以不
+    /// 相容的版本需求再次遇到，視為版本衝突。為了偵測循環依賴，會沿途
+    /// 攜帶目前的祖先路徑，當某個依賴指回路徑上尚未解析完成的節點時，
+    /// 回報為循環而不是無窮迴圈。
+    ///
+    /// 依賴的 [`DependencyKind`] 會被尊重：`Dev` 依賴只有在 `include_dev`
+    /// 為 `true` 時才會被安裝；`Optional` 依賴解析失敗時只會印出警告並
+    /// 跳過，不會讓整個解析失敗；`Peer` 依賴完全不會被排入安裝佇列，而是
+    /// 在走訪結束後確認它們已經被別的依賴以相容版本解析過。
+    ///
+    /// # 參數
+    /// - `root`: 要解析的根套件名稱
+    /// - `req`: 根套件的版本需求
+    /// - `include_dev`: 是否安裝 `Dev` 依賴
+    ///
+    /// # 回傳
+    /// 回傳依解析順序排列的 [`ResolvedPackage`] 清單；遇到版本衝突、循環
+    /// 依賴或未滿足的 peer 依賴時回傳 `CoreError::DependencyError`
+    pub fn resolve_tree(
+        &self,
+        root: &str,
+        req: &str,
+        include_dev: bool,
+    ) -> CoreResult<Vec<ResolvedPackage>> {
+        let mut queue: VecDeque<(String, String, Vec<String>)> = VecDeque::new();
+        queue.push_back((root.to_string(), req.to_string(), Vec::new()));
+
+        let mut chosen: HashMap<String, Version> = HashMap::new();
+        let mut output = Vec::new();
+        // (依賴方, peer 套件名稱, peer 版本需求)，留到走訪結束後統一檢查
+        let mut pending_peers: Vec<(String, String, String)> = Vec::new();
+
+        while let Some((name, requirement, ancestry)) = queue.pop_front() {
+            if ancestry.contains(&name) {
+                return Err(CoreError::DependencyError(format!(
+                    "dependency cycle detected: {} -> {name}",
+                    ancestry.join(" -> ")
+                )));
+            }
+
+            if let Some(existing) = chosen.get(&name) {
+                let parsed_req = VersionReq::from_str(&requirement).map_err(|_| {
+                    CoreError::DependencyError(format!(
+                        "invalid version requirement '{requirement}' for '{name}'"
+                    ))
+                })?;
+                if !parsed_req.matches(existing) {
+                    return Err(CoreError::DependencyError(format!(
+                        "conflicting requirement for '{name}': already resolved to {existing}, but also requires '{requirement}'"
+                    )));
+                }
+                continue;
+            }
+
+            let package = self.resolve_version(&name, &requirement)?;
+            let version = Version::from_str(&package.version)?;
+            chosen.insert(name.clone(), version.clone());
+            output.push(ResolvedPackage {
+                name: name.clone(),
+                version,
+                url: package.url.clone(),
+                hash: package.hash.clone(),
+            });
+
+            let mut path = ancestry;
+            path.push(name.clone());
+            if let Some(dependencies) = &package.dependencies {
+                for dep in dependencies {
+                    match dep.kind {
+                        DependencyKind::Dev if !include_dev => continue,
+                        DependencyKind::Peer => {
+                            pending_peers.push((name.clone(), dep.name.clone(), dep.version.clone()));
+                            continue;
+                        }
+                        DependencyKind::Optional
+                            if self.resolve_version(&dep.name, &dep.version).is_err() =>
+                        {
+                            eprintln!(
+                                "warning: optional dependency '{}' of '{}' could not be resolved, skipping",
+                                dep.name, name
+                            );
+                            continue;
+                        }
+                        _ => {}
+                    }
+                    queue.push_back((dep.name.clone(), dep.version.clone(), path.clone()));
+                }
+            }
+        }
+
+        for (dependent, peer_name, peer_req) in pending_peers {
+            let requirement = VersionReq::from_str(&peer_req).map_err(|_| {
+                CoreError::DependencyError(format!(
+                    "invalid peer version requirement '{peer_req}' for '{peer_name}' (required by '{dependent}')"
+                ))
+            })?;
+            match chosen.get(&peer_name) {
+                Some(version) if requirement.matches(version) => {}
+                Some(version) => {
+                    return Err(CoreError::DependencyError(format!(
+                        "peer dependency '{peer_name}' required by '{dependent}' as '{peer_req}' is not satisfied by resolved version {version}"
+                    )));
+                }
+                None => {
+                    return Err(CoreError::DependencyError(format!(
+                        "peer dependency '{peer_name}' required by '{dependent}' as '{peer_req}' was never installed"
+                    )));
+                }
+            }
+        }
+
+        Ok(output)
+    }
+}
@@ -0,0 +1,231 @@
+//! 語義化版本（SemVer）解析與需求比對
+//!
+//! `Dependency.version` 是一個版本需求（例如 `^1.2.0`、`~1.2.0`、
+//! `>=1.0.0, <2.0.0`、`*`、`latest`），而 `PackageBasicInfo.version`
+//! 則是一個已發布的具體版本。這個模組把兩者分開成 [`VersionReq`] 與
+//! [`Version`]，讓 `RepoInfo` 可以判斷某個具體版本是否滿足一個需求。
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{de::Error as DeError, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{CoreError, CoreResult};
+
+/// 一個具體、已發布的語義化版本，例如 `1.2.3`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+}
+
+impl Version {
+    /// 建立一個新的 `Version`
+    pub fn new(major: u64, minor: u64, patch: u64) -> Self {
+        Version { major, minor, patch }
+    }
+
+    /// 滿足 caret 需求（`^`）時允許的最大版本（不含）
+    fn caret_ceiling(&self) -> Version {
+        if self.major > 0 {
+            Version::new(self.major + 1, 0, 0)
+        } else if self.minor > 0 {
+            Version::new(0, self.minor + 1, 0)
+        } else {
+            Version::new(0, 0, self.patch + 1)
+        }
+    }
+
+    /// 滿足 tilde 需求（`~`）時允許的最大版本（不含）
+    fn tilde_ceiling(&self) -> Version {
+        Version::new(self.major, self.minor + 1, 0)
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.major, self.minor, self.patch).cmp(&(other.major, other.minor, other.patch))
+    }
+}
+
+// 以字串表示序列化/反序列化，讓 `Version` 可以直接當作 JSON 物件的 key
+// （例如 `HashMap<Version, PackageBasicInfo>`）。
+impl Serialize for Version {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Version {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Version::from_str(&s).map_err(DeError::custom)
+    }
+}
+
+impl FromStr for Version {
+    type Err = CoreError;
+
+    fn from_str(s: &str) -> CoreResult<Self> {
+        let s = s.trim();
+        let mut parts = s.splitn(3, '.');
+        let major = parts
+            .next()
+            .filter(|p| !p.is_empty())
+            .and_then(|p| p.parse().ok())
+            .ok_or_else(|| CoreError::VersionMismatch(format!("invalid version: '{s}'")))?;
+        let minor = match parts.next() {
+            Some(p) => p
+                .parse()
+                .map_err(|_| CoreError::VersionMismatch(format!("invalid version: '{s}'")))?,
+            None => 0,
+        };
+        let patch = match parts.next() {
+            Some(p) => p
+                .parse()
+                .map_err(|_| CoreError::VersionMismatch(format!("invalid version: '{s}'")))?,
+            None => 0,
+        };
+        Ok(Version { major, minor, patch })
+    }
+}
+
+/// 單一比較運算子加上一個版本所組成的邊界，用於 [`VersionReq::Range`]
+///
+/// 公開這個型別是因為它透過 `VersionReq::Range` 出現在公開 API 裡；
+/// 範圍通常經由 `VersionReq` 的 `FromStr` 解析取得，但由於兩者的變體都是
+/// 公開的，呼叫端也可以直接建構 `VersionReq::Range`。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Bound {
+    /// `>=`，大於等於
+    AtLeast(Version),
+    /// `>`，嚴格大於
+    GreaterThan(Version),
+    /// `<=`，小於等於
+    AtMost(Version),
+    /// `<`，嚴格小於
+    LessThan(Version),
+    /// `=`，精確等於
+    Equal(Version),
+}
+
+impl Bound {
+    fn matches(&self, v: &Version) -> bool {
+        match self {
+            Bound::AtLeast(b) => v >= b,
+            Bound::GreaterThan(b) => v > b,
+            Bound::AtMost(b) => v <= b,
+            Bound::LessThan(b) => v < b,
+            Bound::Equal(b) => v == b,
+        }
+    }
+
+    fn parse(clause: &str) -> CoreResult<Bound> {
+        let clause = clause.trim();
+        let (op, rest) = if let Some(rest) = clause.strip_prefix(">=") {
+            (">=", rest)
+        } else if let Some(rest) = clause.strip_prefix("<=") {
+            ("<=", rest)
+        } else if let Some(rest) = clause.strip_prefix('>') {
+            (">", rest)
+        } else if let Some(rest) = clause.strip_prefix('<') {
+            ("<", rest)
+        } else if let Some(rest) = clause.strip_prefix('=') {
+            ("=", rest)
+        } else {
+            ("=", clause)
+        };
+        let version = Version::from_str(rest.trim())?;
+        Ok(match op {
+            ">=" => Bound::AtLeast(version),
+            "<=" => Bound::AtMost(version),
+            ">" => Bound::GreaterThan(version),
+            "<" => Bound::LessThan(version),
+            _ => Bound::Equal(version),
+        })
+    }
+}
+
+/// `Dependency.version` 的解析結果：一個版本需求
+///
+/// 支援的語法：
+/// - `*`：符合任何版本
+/// - `latest`：交由呼叫端決定何謂「最新」（通常搭配 dist-tag）
+/// - `1.2.3`：精確版本
+/// - `^1.2.3`：相容於 `1.2.3`，允許更新的次版本/修訂版本
+/// - `~1.2.3`：相容於 `1.2.3`，只允許更新的修訂版本
+/// - `>=1.0.0, <2.0.0`：以逗號分隔的比較子句組成的範圍
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VersionReq {
+    Wildcard,
+    Latest,
+    Exact(Version),
+    Caret(Version),
+    Tilde(Version),
+    Range(Vec<Bound>),
+}
+
+impl VersionReq {
+    /// 判斷一個具體版本是否滿足這個需求
+    pub fn matches(&self, version: &Version) -> bool {
+        match self {
+            VersionReq::Wildcard | VersionReq::Latest => true,
+            VersionReq::Exact(v) => version == v,
+            VersionReq::Caret(v) => version >= v && version < &v.caret_ceiling(),
+            VersionReq::Tilde(v) => version >= v && version < &v.tilde_ceiling(),
+            VersionReq::Range(bounds) => bounds.iter().all(|b| b.matches(version)),
+        }
+    }
+}
+
+impl FromStr for VersionReq {
+    type Err = CoreError;
+
+    fn from_str(s: &str) -> CoreResult<Self> {
+        let trimmed = s.trim();
+        if trimmed == "*" {
+            return Ok(VersionReq::Wildcard);
+        }
+        if trimmed.eq_ignore_ascii_case("latest") {
+            return Ok(VersionReq::Latest);
+        }
+        if let Some(rest) = trimmed.strip_prefix('^') {
+            return Ok(VersionReq::Caret(Version::from_str(rest)?));
+        }
+        if let Some(rest) = trimmed.strip_prefix('~') {
+            return Ok(VersionReq::Tilde(Version::from_str(rest)?));
+        }
+        if trimmed.contains(',')
+            || trimmed.starts_with(">=")
+            || trimmed.starts_with("<=")
+            || trimmed.starts_with('>')
+            || trimmed.starts_with('<')
+        {
+            let bounds = trimmed
+                .split(',')
+                .map(Bound::parse)
+                .collect::<CoreResult<Vec<_>>>()?;
+            return Ok(VersionReq::Range(bounds));
+        }
+        Ok(VersionReq::Exact(Version::from_str(trimmed)?))
+    }
+}
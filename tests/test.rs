@@ -36,7 +36,7 @@ mod tests {
                 None,
             );
 
-            let removed_package = repo.remove_package("package1").unwrap();
+            let removed_package = repo.remove_package("package1", "1.0.0").unwrap();
             assert_eq!(removed_package.version, "1.0.0");
             assert!(!repo.has_package("package1"));
         }
@@ -55,16 +55,49 @@ mod tests {
 
             repo.update_package(
                 "package1",
+                "1.0.0",
                 Some("http://example.com/new".to_string()),
                 None,
-                Some("2.0.0".to_string()),
                 None,
                 None,
-            );
+            )
+            .unwrap();
 
             let package = repo.get_package("package1").unwrap();
             assert_eq!(package.url, "http://example.com/new");
-            assert_eq!(package.version, "2.0.0");
+            assert_eq!(package.version, "1.0.0");
+        }
+
+        #[test]
+        fn test_multiple_versions_and_dist_tags() {
+            let mut repo = RepoInfo::new();
+            repo.add_package(
+                "package1".to_string(),
+                "http://example.com/1.0.0".to_string(),
+                "file1.zip".to_string(),
+                "1.0.0".to_string(),
+                "hash123".to_string(),
+                None,
+            );
+            repo.add_package(
+                "package1".to_string(),
+                "http://example.com/2.0.0-beta".to_string(),
+                "file1.zip".to_string(),
+                "2.0.0".to_string(),
+                "hash456".to_string(),
+                None,
+            );
+            repo.set_dist_tag("package1", "beta", "2.0.0").unwrap();
+
+            // `latest` automatically follows the highest published version
+            let latest = repo.get_package("package1").unwrap();
+            assert_eq!(latest.version, "2.0.0");
+
+            let beta = repo.get_tagged("package1", "beta").unwrap();
+            assert_eq!(beta.version, "2.0.0");
+
+            let stable = repo.resolve_version("package1", "^1.0.0").unwrap();
+            assert_eq!(stable.version, "1.0.0");
         }
 
         #[test]
@@ -73,6 +106,245 @@ mod tests {
             let result = repo.get_package("nonexistent");
             assert!(result.is_err());
         }
+
+        #[test]
+        fn test_resolve_tree_simple() {
+            let mut repo = RepoInfo::new();
+            repo.add_package(
+                "app".to_string(),
+                "http://example.com/app".to_string(),
+                "app.zip".to_string(),
+                "1.0.0".to_string(),
+                "hash-app".to_string(),
+                Some(vec![Dependency::new("lib", "^1.0.0")]),
+            );
+            repo.add_package(
+                "lib".to_string(),
+                "http://example.com/lib".to_string(),
+                "lib.zip".to_string(),
+                "1.2.0".to_string(),
+                "hash-lib".to_string(),
+                None,
+            );
+
+            let resolved = repo.resolve_tree("app", "^1.0.0", false).unwrap();
+            assert_eq!(resolved.len(), 2);
+            assert_eq!(resolved[0].name, "app");
+            assert_eq!(resolved[1].name, "lib");
+            assert_eq!(resolved[1].version.to_string(), "1.2.0");
+        }
+
+        #[test]
+        fn test_resolve_tree_conflict() {
+            let mut repo = RepoInfo::new();
+            repo.add_package(
+                "app".to_string(),
+                "http://example.com/app".to_string(),
+                "app.zip".to_string(),
+                "1.0.0".to_string(),
+                "hash-app".to_string(),
+                Some(vec![
+                    Dependency::new("lib", "^1.0.0"),
+                    Dependency::new("other", "^1.0.0"),
+                ]),
+            );
+            repo.add_package(
+                "lib".to_string(),
+                "http://example.com/lib".to_string(),
+                "lib.zip".to_string(),
+                "1.0.0".to_string(),
+                "hash-lib".to_string(),
+                Some(vec![Dependency::new("shared", "^2.0.0")]),
+            );
+            repo.add_package(
+                "other".to_string(),
+                "http://example.com/other".to_string(),
+                "other.zip".to_string(),
+                "1.0.0".to_string(),
+                "hash-other".to_string(),
+                Some(vec![Dependency::new("shared", "^1.0.0")]),
+            );
+            repo.add_package(
+                "shared".to_string(),
+                "http://example.com/shared-1".to_string(),
+                "shared.zip".to_string(),
+                "1.5.0".to_string(),
+                "hash-shared-1".to_string(),
+                None,
+            );
+            repo.add_package(
+                "shared".to_string(),
+                "http://example.com/shared-2".to_string(),
+                "shared.zip".to_string(),
+                "2.5.0".to_string(),
+                "hash-shared-2".to_string(),
+                None,
+            );
+
+            let err = repo.resolve_tree("app", "^1.0.0", false).unwrap_err();
+            assert!(matches!(err, CoreError::DependencyError(_)));
+        }
+
+        #[test]
+        fn test_resolve_tree_cycle() {
+            let mut repo = RepoInfo::new();
+            repo.add_package(
+                "a".to_string(),
+                "http://example.com/a".to_string(),
+                "a.zip".to_string(),
+                "1.0.0".to_string(),
+                "hash-a".to_string(),
+                Some(vec![Dependency::new("b", "^1.0.0")]),
+            );
+            repo.add_package(
+                "b".to_string(),
+                "http://example.com/b".to_string(),
+                "b.zip".to_string(),
+                "1.0.0".to_string(),
+                "hash-b".to_string(),
+                Some(vec![Dependency::new("a", "^1.0.0")]),
+            );
+
+            let err = repo.resolve_tree("a", "^1.0.0", false).unwrap_err();
+            assert!(matches!(err, CoreError::DependencyError(_)));
+        }
+
+        #[test]
+        fn test_lockfile_from_resolution_and_verify() {
+            let mut repo = RepoInfo::new();
+            repo.add_package(
+                "app".to_string(),
+                "http://example.com/app".to_string(),
+                "app.zip".to_string(),
+                "1.0.0".to_string(),
+                "hash-app".to_string(),
+                Some(vec![Dependency::new("lib", "^1.0.0")]),
+            );
+            repo.add_package(
+                "lib".to_string(),
+                "http://example.com/lib".to_string(),
+                "lib.zip".to_string(),
+                "1.2.0".to_string(),
+                "hash-lib".to_string(),
+                None,
+            );
+
+            let resolved = repo.resolve_tree("app", "^1.0.0", false).unwrap();
+            let lockfile = LockFile::from_resolution(&resolved);
+            assert_eq!(lockfile.lockfile_version, LOCKFILE_FORMAT_VERSION);
+            assert_eq!(lockfile.packages.len(), 2);
+            assert!(lockfile.verify(&repo).is_ok());
+
+            // Republishing "lib" at the same version with a different hash should
+            // be detected as drift by `verify`.
+            repo.update_package(
+                "lib",
+                "1.2.0",
+                None,
+                None,
+                Some("hash-lib-tampered".to_string()),
+                None,
+            )
+            .unwrap();
+            let err = lockfile.verify(&repo).unwrap_err();
+            assert!(matches!(err, CoreError::HashMismatch { .. }));
+        }
+
+        #[test]
+        fn test_resolve_tree_dev_dependency_gated_by_include_dev() {
+            let mut repo = RepoInfo::new();
+            repo.add_package(
+                "app".to_string(),
+                "http://example.com/app".to_string(),
+                "app.zip".to_string(),
+                "1.0.0".to_string(),
+                "hash-app".to_string(),
+                Some(vec![Dependency::with_kind(
+                    "test-framework",
+                    "^1.0.0",
+                    DependencyKind::Dev,
+                )]),
+            );
+            repo.add_package(
+                "test-framework".to_string(),
+                "http://example.com/test-framework".to_string(),
+                "test-framework.zip".to_string(),
+                "1.0.0".to_string(),
+                "hash-test-framework".to_string(),
+                None,
+            );
+
+            let without_dev = repo.resolve_tree("app", "^1.0.0", false).unwrap();
+            assert_eq!(without_dev.len(), 1);
+
+            let with_dev = repo.resolve_tree("app", "^1.0.0", true).unwrap();
+            assert_eq!(with_dev.len(), 2);
+        }
+
+        #[test]
+        fn test_resolve_tree_optional_dependency_skipped_on_failure() {
+            let mut repo = RepoInfo::new();
+            repo.add_package(
+                "app".to_string(),
+                "http://example.com/app".to_string(),
+                "app.zip".to_string(),
+                "1.0.0".to_string(),
+                "hash-app".to_string(),
+                Some(vec![Dependency::with_kind(
+                    "missing-optional",
+                    "^1.0.0",
+                    DependencyKind::Optional,
+                )]),
+            );
+
+            let resolved = repo.resolve_tree("app", "^1.0.0", false).unwrap();
+            assert_eq!(resolved.len(), 1);
+            assert_eq!(resolved[0].name, "app");
+        }
+
+        #[test]
+        fn test_resolve_tree_peer_dependency_checked_not_installed() {
+            let mut repo = RepoInfo::new();
+            repo.add_package(
+                "app".to_string(),
+                "http://example.com/app".to_string(),
+                "app.zip".to_string(),
+                "1.0.0".to_string(),
+                "hash-app".to_string(),
+                Some(vec![
+                    Dependency::new("lib", "^1.0.0"),
+                    Dependency::with_kind("lib", "^1.0.0", DependencyKind::Peer),
+                ]),
+            );
+            repo.add_package(
+                "lib".to_string(),
+                "http://example.com/lib".to_string(),
+                "lib.zip".to_string(),
+                "1.2.0".to_string(),
+                "hash-lib".to_string(),
+                None,
+            );
+
+            // "lib" is resolved once (as a runtime dependency); the peer entry is
+            // satisfied by it rather than installing a second copy.
+            let resolved = repo.resolve_tree("app", "^1.0.0", false).unwrap();
+            assert_eq!(resolved.len(), 2);
+
+            repo.add_package(
+                "standalone".to_string(),
+                "http://example.com/standalone".to_string(),
+                "standalone.zip".to_string(),
+                "1.0.0".to_string(),
+                "hash-standalone".to_string(),
+                Some(vec![Dependency::with_kind(
+                    "never-installed",
+                    "^1.0.0",
+                    DependencyKind::Peer,
+                )]),
+            );
+            let err = repo.resolve_tree("standalone", "^1.0.0", false).unwrap_err();
+            assert!(matches!(err, CoreError::DependencyError(_)));
+        }
     }
 
     // 測試 JsonStorage 功能
@@ -147,13 +419,127 @@ mod tests {
         let dependency = Dependency {
             name: "serde".to_string(),
             version: "1.0.0".to_string(),
+            kind: DependencyKind::Runtime,
         };
 
         let serialized = serde_json::to_string(&dependency).unwrap();
         assert!(serialized.contains("\"name\":\"serde\""));
+        assert!(!serialized.contains("kind"));
 
         let deserialized: Dependency = serde_json::from_str(&serialized).unwrap();
         assert_eq!(deserialized.name, "serde");
         assert_eq!(deserialized.version, "1.0.0");
     }
+
+    // 測試版本需求比對功能
+    #[test]
+    fn test_version_req_caret() {
+        let req: VersionReq = "^1.2.0".parse().unwrap();
+        assert!(req.matches(&"1.2.0".parse().unwrap()));
+        assert!(req.matches(&"1.9.9".parse().unwrap()));
+        assert!(!req.matches(&"2.0.0".parse().unwrap()));
+        assert!(!req.matches(&"1.1.9".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_version_req_tilde() {
+        let req: VersionReq = "~1.2.0".parse().unwrap();
+        assert!(req.matches(&"1.2.9".parse().unwrap()));
+        assert!(!req.matches(&"1.3.0".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_version_req_range() {
+        let req: VersionReq = ">=1.0.0, <2.0.0".parse().unwrap();
+        assert!(req.matches(&"1.5.0".parse().unwrap()));
+        assert!(!req.matches(&"2.0.0".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_version_req_wildcard_and_latest() {
+        let wildcard: VersionReq = "*".parse().unwrap();
+        let latest: VersionReq = "latest".parse().unwrap();
+        let version: Version = "3.4.5".parse().unwrap();
+        assert!(wildcard.matches(&version));
+        assert!(latest.matches(&version));
+    }
+
+    #[cfg(feature = "server")]
+    #[test]
+    fn test_resolve_version() {
+        let mut repo = RepoInfo::new();
+        repo.add_package(
+            "package1".to_string(),
+            "http://example.com".to_string(),
+            "file1.zip".to_string(),
+            "1.2.3".to_string(),
+            "hash123".to_string(),
+            None,
+        );
+
+        let resolved = repo.resolve_version("package1", "^1.2.0").unwrap();
+        assert_eq!(resolved.version, "1.2.3");
+
+        let err = repo.resolve_version("package1", "^2.0.0").unwrap_err();
+        assert!(matches!(err, CoreError::VersionMismatch(_)));
+    }
+
+    // 測試完整性雜湊（Integrity）功能
+    #[test]
+    fn test_integrity_parse_and_display() {
+        let integrity: Integrity = "sha256-47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU="
+            .parse()
+            .unwrap();
+        assert_eq!(integrity.algorithm, HashAlgorithm::Sha256);
+        assert_eq!(
+            integrity.to_string(),
+            "sha256-47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU="
+        );
+    }
+
+    #[test]
+    fn test_integrity_invalid_format() {
+        assert!("not-a-valid-algo-digest".parse::<Integrity>().is_err());
+        assert!("sha256-".parse::<Integrity>().is_err());
+    }
+
+    #[test]
+    fn test_integrity_from_reader_and_verify() {
+        let data = b"hello world";
+        let integrity = Integrity::from_reader(HashAlgorithm::Sha256, &mut &data[..]).unwrap();
+        assert!(integrity.verify(&integrity.digest).is_ok());
+
+        let mismatched: Integrity = format!("sha256-{}", "A".repeat(integrity.digest.len()))
+            .parse()
+            .unwrap();
+        let err = mismatched.verify(&integrity.digest).unwrap_err();
+        assert!(matches!(err, CoreError::HashMismatch { .. }));
+    }
+
+    // 測試內容雜湊定址快取
+    #[test]
+    fn test_cache_put_and_get() {
+        let root = std::env::temp_dir().join("dpm_core_cache_test");
+        let _ = std::fs::remove_dir_all(&root);
+        let cache = Cache::new(&root).unwrap();
+
+        let integrity =
+            Integrity::from_reader(HashAlgorithm::Sha256, &mut &b"cached content"[..]).unwrap();
+        assert!(cache.get(&integrity).unwrap().is_none());
+
+        let path = cache
+            .put("package1", "1.0.0", &integrity, b"cached content")
+            .unwrap();
+        assert!(path.is_file());
+        assert_eq!(std::fs::read(&path).unwrap(), b"cached content");
+
+        let hit = cache.get(&integrity).unwrap().unwrap();
+        assert_eq!(hit, path);
+
+        let indexed = cache.lookup("package1", "1.0.0").unwrap().unwrap();
+        assert_eq!(indexed, integrity);
+        assert!(cache.lookup("package1", "2.0.0").unwrap().is_none());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
 }